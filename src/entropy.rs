@@ -0,0 +1,156 @@
+//! An OS-entropy-first fallback source, modeled on rand's `EntropyRng`.
+
+use crate::{JitterError, Jitterbug};
+use rand_core::{TryCryptoRng, TryRngCore};
+
+/// Identifies which backend served the most recent
+/// [`EntropySource`] request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// The operating system RNG, via `getrandom`.
+    Os,
+    /// The [`Jitterbug`] fallback collector.
+    Jitter,
+}
+
+/// [`EntropySource`] prefers the operating system RNG and falls back to
+/// [`Jitterbug`] only when the OS source is unavailable or errors,
+/// retrying the OS source on every subsequent call rather than sticking
+/// with the fallback. If both the OS and the jitter health tests fail,
+/// requests return a [`JitterError`] instead of silently yielding weak
+/// output.
+pub struct EntropySource {
+    /// [`jitter`](EntropySource::jitter) is the fallback collector, built
+    /// lazily the first time the OS source fails.
+    jitter: Option<Jitterbug>,
+    /// [`last_backend`](EntropySource::last_backend) records which
+    /// backend served the most recent request.
+    last_backend: Backend,
+}
+
+impl Default for EntropySource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EntropySource {
+    /// Creates a `new` [`EntropySource`]. No [`Jitterbug`] is constructed
+    /// until the OS source first fails, since it is never needed
+    /// otherwise.
+    pub fn new() -> Self {
+        Self {
+            jitter: None,
+            last_backend: Backend::Os,
+        }
+    }
+
+    /// Reports which [`Backend`] served the most recent request.
+    pub fn last_backend(&self) -> Backend {
+        self.last_backend
+    }
+
+    fn jitter(&mut self) -> Result<&mut Jitterbug, JitterError> {
+        if self.jitter.is_none() {
+            self.jitter = Some(Jitterbug::try_new()?);
+        }
+        Ok(self.jitter.as_mut().expect("jitter was just initialized"))
+    }
+
+    /// Selects a backend for `dest` given the already-attempted OS call's
+    /// `os_result`, recording it via [`last_backend`](EntropySource::last_backend).
+    /// Factored out of [`try_fill_bytes`](TryRngCore::try_fill_bytes) so
+    /// tests can drive the `Jitter` branch with a synthetic OS failure,
+    /// since `getrandom::getrandom` itself can't be forced to fail.
+    fn fill_from(
+        &mut self,
+        dest: &mut [u8],
+        os_result: Result<(), getrandom::Error>,
+    ) -> Result<(), JitterError> {
+        match os_result {
+            Ok(()) => {
+                self.last_backend = Backend::Os;
+                Ok(())
+            }
+            Err(_) => {
+                self.last_backend = Backend::Jitter;
+                self.jitter()?.try_fill_bytes(dest)
+            }
+        }
+    }
+}
+
+/// Implement [`TryRngCore`] for [`EntropySource`]
+impl TryRngCore for EntropySource {
+    type Error = JitterError;
+
+    fn try_next_u32(&mut self) -> Result<u32, Self::Error> {
+        let mut bytes = [0u8; 4];
+        self.try_fill_bytes(&mut bytes)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn try_next_u64(&mut self) -> Result<u64, Self::Error> {
+        let mut bytes = [0u8; 8];
+        self.try_fill_bytes(&mut bytes)?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Self::Error> {
+        let os_result = getrandom::getrandom(dest);
+        self.fill_from(dest, os_result)
+    }
+}
+
+/// Implement [`TryCryptoRng`] for [`EntropySource`]
+impl TryCryptoRng for EntropySource {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_reports_the_os_backend_before_any_request() {
+        let source = EntropySource::new();
+        assert_eq!(source.last_backend(), Backend::Os);
+    }
+
+    #[test]
+    fn successful_requests_keep_reporting_the_os_backend() {
+        let mut source = EntropySource::new();
+        for _ in 0..8 {
+            source
+                .try_next_u64()
+                .expect("the OS RNG should be available in this environment");
+            assert_eq!(source.last_backend(), Backend::Os);
+        }
+    }
+
+    #[test]
+    fn a_synthetic_os_failure_falls_back_to_jitter() {
+        let mut source = EntropySource::new();
+        let mut dest = [0u8; 8];
+
+        source
+            .fill_from(&mut dest, Err(getrandom::Error::UNSUPPORTED))
+            .expect("the jitter fallback should still produce output");
+
+        assert_eq!(source.last_backend(), Backend::Jitter);
+    }
+
+    #[test]
+    fn a_later_successful_call_reports_the_os_backend_again() {
+        let mut source = EntropySource::new();
+        let mut dest = [0u8; 8];
+
+        source
+            .fill_from(&mut dest, Err(getrandom::Error::UNSUPPORTED))
+            .expect("the jitter fallback should still produce output");
+        assert_eq!(source.last_backend(), Backend::Jitter);
+
+        source
+            .try_next_u64()
+            .expect("the OS RNG should be available in this environment");
+        assert_eq!(source.last_backend(), Backend::Os);
+    }
+}
@@ -1,7 +1,15 @@
-use rand_core::{TryCryptoRng, TryRng};
-use std::arch::x86_64::_rdtsc;
+mod entropy;
+mod health;
+mod platform;
+mod reseeding;
+
+use health::HealthState;
+use platform::{ActiveTimer, Timer};
+pub use entropy::{Backend, EntropySource};
+pub use reseeding::ReseedingJitter;
+use rand_core::{TryCryptoRng, TryRngCore};
 use std::collections::hash_map::DefaultHasher;
-use std::convert::Infallible;
+use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::hint::black_box;
 use std::sync::Arc;
@@ -10,6 +18,69 @@ use std::thread;
 
 const BACKGROUND_NOISE: u64 = 0x517cc1b727220a95;
 
+/// The number of raw timer deltas collected per [`Jitterbug::harvest`]
+/// round.
+const HARVEST_SAMPLES: usize = 256;
+
+/// The multiplier applied to [`HARVEST_SAMPLES`] for the amplified startup
+/// health test run once at construction, per SP 800-90B section 4.3.
+const STARTUP_SAMPLE_MULTIPLIER: usize = 4;
+
+/// The number of back-to-back deltas collected by
+/// [`Jitterbug::test_timer`] to assess whether the platform timer has
+/// enough resolution to observe CPU jitter at all.
+const TIMER_TEST_SAMPLES: usize = 256;
+
+/// The minimum number of distinct low bits that must change across the
+/// samples collected by [`Jitterbug::test_timer`] for the timer to be
+/// considered usable.
+const TIMER_TEST_MIN_VARYING_BITS: u32 = 1;
+
+/// The default size, in bytes, of the memory-walk scratch region — larger
+/// than a typical L1 data cache so the walk below reliably produces cache
+/// misses.
+const DEFAULT_MEMORY_SIZE: usize = 64 * 1024;
+
+/// The default number of data-dependent memory accesses performed across
+/// the scratch region per measurement.
+const DEFAULT_MEMORY_ACCESSES: usize = 64;
+
+/// An error surfaced when [`Jitterbug`] cannot trust the entropy it has
+/// collected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JitterError {
+    /// The NIST SP 800-90B Repetition Count Test failed: the same raw
+    /// delta was observed too many times in a row, suggesting a stuck
+    /// counter or a timer too coarse to capture CPU jitter.
+    RepetitionCountFailure,
+    /// The NIST SP 800-90B Adaptive Proportion Test failed: one raw delta
+    /// value dominated its window, suggesting the entropy source has
+    /// degenerated (e.g. a VM with a pinned or virtualized timer).
+    AdaptiveProportionFailure,
+    /// The platform timer does not have enough resolution to observe CPU
+    /// jitter: its deltas were all zero, showed no bit-level variation, or
+    /// the counter appears frozen. See `Jitterbug::test_timer`.
+    CoarseTimer,
+}
+
+impl fmt::Display for JitterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JitterError::RepetitionCountFailure => {
+                write!(f, "jitter health test failed: repetition count test")
+            }
+            JitterError::AdaptiveProportionFailure => {
+                write!(f, "jitter health test failed: adaptive proportion test")
+            }
+            JitterError::CoarseTimer => {
+                write!(f, "platform timer is too coarse to observe CPU jitter")
+            }
+        }
+    }
+}
+
+impl std::error::Error for JitterError {}
+
 /// A True Random Number Generator (TRNG) based on CPU execution jitter.
 ///
 /// [`Jitterbug`] harvests entropy by measuring subtle timing variations in
@@ -29,6 +100,18 @@ pub struct Jitterbug {
     /// [`last_raw`](Jitterbug::last_raw) is the last unsigned 64-bit integer
     /// that was generated.
     last_raw: u64,
+    /// [`health`](Jitterbug::health) tracks the continuous SP 800-90B
+    /// health tests across every [`harvest`](Jitterbug::harvest) round.
+    health: HealthState,
+    /// [`scratch`](Jitterbug::scratch) is a reusable scratch region,
+    /// larger than most L1 caches, that [`sample_delta`](Jitterbug::sample_delta)
+    /// walks between timer reads to amplify jitter via cache and
+    /// prefetcher timing variance. Empty falls back to an empty spin.
+    scratch: Vec<u8>,
+    /// [`memory_accesses`](Jitterbug::memory_accesses) is the number of
+    /// data-dependent reads/writes performed across
+    /// [`scratch`](Jitterbug::scratch) per measurement.
+    memory_accesses: usize,
 }
 
 /// Implement [`Default`] for [`Jitterbug`]
@@ -41,21 +124,47 @@ impl Default for Jitterbug {
 /// Implement [`Jitterbug`]
 impl Jitterbug {
     /// Creates a `new` [`Jitterbug`] generator.
+    ///
+    /// # Panics
+    /// Panics if [`try_new`](Jitterbug::try_new) fails, which happens when
+    /// the platform timer is too coarse to observe CPU jitter or the
+    /// startup SP 800-90B health test fails. Use
+    /// [`try_new`](Jitterbug::try_new) directly to handle this instead of
+    /// panicking.
+    ///
     /// ## Usage
     /// Use [`new`](Jitterbug::new) to create a [`Jitterbug`]:
     /// ```rust
     /// use jitterbug::Jitterbug;
-    /// use rand_core::Rng;
+    /// use rand_core::TryRngCore;
     ///
-    /// // create a new jitterbug, and unwrap for direct
-    /// // `Infallable` `Result`
+    /// // create a new jitterbug
     /// let mut jitter_rng = Jitterbug::new();
     ///
     /// // generate a new `u64` number
-    /// let random_number = jitter_rng.next_u64();
+    /// let random_number = jitter_rng.try_next_u64().expect("jitter source should not fail");
     /// println!("random number: {random_number}");
     /// ```
     pub fn new() -> Self {
+        Self::try_new().expect("jitterbug: failed to construct a trustworthy entropy source")
+    }
+
+    /// Creates a `new` [`Jitterbug`] generator, returning a [`JitterError`]
+    /// instead of panicking if the platform timer or the harvested entropy
+    /// cannot be trusted.
+    /// ## Usage
+    /// Use [`try_new`](Jitterbug::try_new) to create a [`Jitterbug`]
+    /// without panicking on an untrustworthy platform:
+    /// ```rust
+    /// use jitterbug::Jitterbug;
+    /// use rand_core::TryRngCore;
+    ///
+    /// match Jitterbug::try_new() {
+    ///     Ok(mut jitter_rng) => println!("seeded: {}", jitter_rng.try_next_u32().expect("jitter source should not fail")),
+    ///     Err(error) => eprintln!("cannot trust this platform's jitter: {error}"),
+    /// }
+    /// ```
+    pub fn try_new() -> Result<Self, JitterError> {
         let running = Arc::new(AtomicBool::new(true));
         let r = running.clone();
 
@@ -74,29 +183,121 @@ impl Jitterbug {
             buffer: [0u8; 32],
             index: 32,
             last_raw: 0,
+            health: HealthState::new(),
+            scratch: vec![0u8; DEFAULT_MEMORY_SIZE],
+            memory_accesses: DEFAULT_MEMORY_ACCESSES,
         };
 
-        rng.harvest();
-        rng
+        rng.test_timer()?;
+        rng.startup_test()?;
+        rng.harvest()?;
+        Ok(rng)
     }
 
-    fn harvest(&mut self) {
-        let mut pool = Vec::with_capacity(256);
-        for _ in 0..256 {
-            unsafe {
-                let t1 = _rdtsc();
-                for _ in 0..100 {
-                    black_box(0);
-                }
-                let t2 = _rdtsc();
-                let delta = t2.wrapping_sub(t1);
+    /// Ports the idea behind rand_jitter's `test_timer`: measures whether
+    /// the platform timer has enough resolution to observe CPU jitter
+    /// before any of it is trusted. Collects
+    /// [`TIMER_TEST_SAMPLES`] back-to-back deltas of the current
+    /// [`sample_delta`](Jitterbug::sample_delta) measurement (the memory
+    /// walk, or the `black_box` spin when no scratch region is configured)
+    /// and checks that they are not all zero, that at least
+    /// [`TIMER_TEST_MIN_VARYING_BITS`] low bits vary across samples, and
+    /// that the counter is not frozen.
+    fn test_timer(&mut self) -> Result<(), JitterError> {
+        let mut deltas = Vec::with_capacity(TIMER_TEST_SAMPLES);
+        for _ in 0..TIMER_TEST_SAMPLES {
+            deltas.push(self.sample_delta());
+        }
+        evaluate_timer_deltas(&deltas)
+    }
 
-                if delta == self.last_raw {
-                    thread::yield_now();
-                }
-                self.last_raw = delta;
-                pool.push(delta);
+    /// Runs an amplified, one-shot SP 800-90B startup health test
+    /// (section 4.3) over a fresh [`HealthState`] before the generator is
+    /// ever trusted to produce output.
+    fn startup_test(&mut self) -> Result<(), JitterError> {
+        let mut startup_health = HealthState::new();
+        for _ in 0..HARVEST_SAMPLES * STARTUP_SAMPLE_MULTIPLIER {
+            let delta = self.sample_delta();
+            startup_health.observe(delta)?;
+        }
+        Ok(())
+    }
+
+    /// Configures the memory-walk amplification used by `sample_delta`: a
+    /// reusable scratch `region` of `size` bytes that gets `accesses`
+    /// data-dependent reads/writes per measurement. Passing `size: 0`
+    /// disables the walk and falls back to the empty-spin measurement.
+    ///
+    /// Changing the measurement method invalidates the gating tests
+    /// [`try_new`](Jitterbug::try_new) ran against the *old* method, so
+    /// this re-runs `test_timer` and `startup_test` against a fresh
+    /// `HealthState` before handing back a [`Jitterbug`] that is trusted
+    /// to produce output with the new scratch region.
+    /// ## Usage
+    /// ```rust
+    /// use jitterbug::Jitterbug;
+    ///
+    /// // use a 128 KiB scratch region and 128 accesses per measurement
+    /// let mut jitter_rng = Jitterbug::new()
+    ///     .with_memory(128 * 1024, 128)
+    ///     .expect("jitter source should stay trustworthy with a new scratch region");
+    /// ```
+    pub fn with_memory(mut self, size: usize, accesses: usize) -> Result<Self, JitterError> {
+        self.scratch = vec![0u8; size];
+        self.memory_accesses = accesses;
+        self.health = HealthState::new();
+        self.test_timer()?;
+        self.startup_test()?;
+        // `buffer` still holds bytes harvested under the old configuration;
+        // force the next read to harvest fresh under the newly-validated one.
+        self.index = 32;
+        Ok(self)
+    }
+
+    /// Times one raw [`ActiveTimer`] delta across either a memory walk of
+    /// [`scratch`](Jitterbug::scratch) (when configured) or, as a
+    /// fallback, an empty `black_box` spin.
+    fn sample_delta(&mut self) -> u64 {
+        let t1 = ActiveTimer::read();
+        if self.scratch.is_empty() {
+            for _ in 0..100 {
+                black_box(0);
             }
+        } else {
+            self.memory_walk();
+        }
+        let t2 = ActiveTimer::read();
+        let delta = t2.wrapping_sub(t1);
+
+        if delta == self.last_raw {
+            thread::yield_now();
+        }
+        self.last_raw = delta;
+        delta
+    }
+
+    /// Strides across [`scratch`](Jitterbug::scratch) performing
+    /// data-dependent reads and writes, starting from an offset derived
+    /// from the previous delta, so cache hits/misses and prefetcher
+    /// behavior inject timing variance into the surrounding timer
+    /// reads.
+    fn memory_walk(&mut self) {
+        let len = self.scratch.len();
+        let mut idx = (self.last_raw as usize) % len;
+
+        for _ in 0..self.memory_accesses {
+            let value = black_box(self.scratch[idx]);
+            idx = (idx + value as usize + 1) % len;
+            self.scratch[idx] = self.scratch[idx].wrapping_add(value).wrapping_add(1);
+        }
+    }
+
+    fn harvest(&mut self) -> Result<(), JitterError> {
+        let mut pool = Vec::with_capacity(HARVEST_SAMPLES);
+        for _ in 0..HARVEST_SAMPLES {
+            let delta = self.sample_delta();
+            self.health.observe(delta)?;
+            pool.push(delta);
         }
 
         for salt in 0..4u64 {
@@ -106,12 +307,83 @@ impl Jitterbug {
             self.buffer[salt as usize * 8..(salt as usize + 1) * 8].copy_from_slice(&bytes);
         }
         self.index = 0;
+        Ok(())
+    }
+
+    /// Draws a `f64` uniformly distributed over the half-open interval
+    /// `[0, 1)`, using the Saito-Matsumoto construction: a raw `u64`'s low
+    /// 52 bits become the mantissa of a value in `[1, 2)`, which is then
+    /// shifted down by `1.0`. This avoids the bias that integer division
+    /// would introduce, and prefers masking over shifting since low-order
+    /// bits are as good as high-order bits here.
+    /// ## Usage
+    /// ```rust
+    /// use jitterbug::Jitterbug;
+    ///
+    /// let mut jitter_rng = Jitterbug::new();
+    /// let sample = jitter_rng.try_next_f64().expect("health tests should pass");
+    /// assert!((0.0..1.0).contains(&sample));
+    /// ```
+    pub fn try_next_f64(&mut self) -> Result<f64, JitterError> {
+        let bits = self.try_next_u64()?;
+        Ok(f64_from_bits(bits))
+    }
+
+    /// Draws a `f32` uniformly distributed over the half-open interval
+    /// `[0, 1)`. See [`try_next_f64`](Jitterbug::try_next_f64) for the
+    /// construction.
+    pub fn try_next_f32(&mut self) -> Result<f32, JitterError> {
+        let bits = self.try_next_u32()?;
+        Ok(f32_from_bits(bits))
     }
 }
 
-/// Implement [`TryRng`] for [`Jitterbug`]
-impl TryRng for Jitterbug {
-    type Error = Infallible;
+/// Checks whether a run of back-to-back timer `deltas` shows enough
+/// resolution to observe CPU jitter: not all zero, not frozen solid, and
+/// varying by at least [`TIMER_TEST_MIN_VARYING_BITS`] low bits across
+/// samples. See [`Jitterbug::test_timer`] for how `deltas` are collected.
+fn evaluate_timer_deltas(deltas: &[u64]) -> Result<(), JitterError> {
+    if deltas.iter().all(|&delta| delta == 0) {
+        return Err(JitterError::CoarseTimer);
+    }
+    if deltas.windows(2).all(|pair| pair[0] == pair[1]) {
+        return Err(JitterError::CoarseTimer);
+    }
+
+    let varying_bits = deltas
+        .windows(2)
+        .fold(0u64, |acc, pair| acc | (pair[0] ^ pair[1]))
+        .count_ones();
+    if varying_bits < TIMER_TEST_MIN_VARYING_BITS {
+        return Err(JitterError::CoarseTimer);
+    }
+
+    Ok(())
+}
+
+/// Applies the Saito-Matsumoto construction to a raw `u64`: its low 52
+/// bits become the mantissa of a value in `[1, 2)`, which is then shifted
+/// down by `1.0` to land in `[0, 1)`.
+fn f64_from_bits(bits: u64) -> f64 {
+    const MANTISSA_MASK: u64 = (1 << 52) - 1;
+    const EXPONENT_ONE: u64 = 1023 << 52;
+
+    let value = f64::from_bits((bits & MANTISSA_MASK) | EXPONENT_ONE);
+    value - 1.0
+}
+
+/// The `f32` counterpart of [`f64_from_bits`].
+fn f32_from_bits(bits: u32) -> f32 {
+    const MANTISSA_MASK: u32 = (1 << 23) - 1;
+    const EXPONENT_ONE: u32 = 127 << 23;
+
+    let value = f32::from_bits((bits & MANTISSA_MASK) | EXPONENT_ONE);
+    value - 1.0
+}
+
+/// Implement [`TryRngCore`] for [`Jitterbug`]
+impl TryRngCore for Jitterbug {
+    type Error = JitterError;
 
     fn try_next_u32(&mut self) -> Result<u32, Self::Error> {
         let mut bytes = [0u8; 4];
@@ -128,7 +400,7 @@ impl TryRng for Jitterbug {
     fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Self::Error> {
         for byte in dest.iter_mut() {
             if self.index >= 32 {
-                self.harvest();
+                self.harvest()?;
             }
             *byte = self.buffer[self.index];
             self.index += 1;
@@ -149,3 +421,134 @@ impl Drop for Jitterbug {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jitterbug_for_memory_walk(size: usize, accesses: usize) -> Jitterbug {
+        Jitterbug {
+            running: Arc::new(AtomicBool::new(false)),
+            disruptor: None,
+            buffer: [0u8; 32],
+            index: 32,
+            last_raw: 0,
+            health: HealthState::new(),
+            scratch: vec![0u8; size],
+            memory_accesses: accesses,
+        }
+    }
+
+    #[test]
+    fn memory_walk_never_indexes_out_of_bounds() {
+        for size in 1..=8 {
+            for accesses in 0..=32 {
+                let mut jitter = jitterbug_for_memory_walk(size, accesses);
+                for _ in 0..8 {
+                    jitter.memory_walk();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn memory_walk_wraps_the_starting_index_from_last_raw() {
+        let mut jitter = jitterbug_for_memory_walk(4, 4);
+        jitter.last_raw = u64::MAX;
+        jitter.memory_walk();
+    }
+
+    #[test]
+    fn evaluate_timer_deltas_rejects_an_all_zero_run() {
+        let deltas = vec![0u64; TIMER_TEST_SAMPLES];
+        assert_eq!(
+            evaluate_timer_deltas(&deltas),
+            Err(JitterError::CoarseTimer)
+        );
+    }
+
+    #[test]
+    fn evaluate_timer_deltas_rejects_a_frozen_counter() {
+        let deltas = vec![7u64; TIMER_TEST_SAMPLES];
+        assert_eq!(
+            evaluate_timer_deltas(&deltas),
+            Err(JitterError::CoarseTimer)
+        );
+    }
+
+    #[test]
+    fn evaluate_timer_deltas_accepts_a_single_varying_bit() {
+        // Alternates between two deltas that differ in exactly one bit:
+        // not frozen, and meets `TIMER_TEST_MIN_VARYING_BITS` exactly.
+        let deltas: Vec<u64> = (0..TIMER_TEST_SAMPLES)
+            .map(|i| if i % 2 == 0 { 0 } else { 1 })
+            .collect();
+        assert_eq!(evaluate_timer_deltas(&deltas), Ok(()));
+    }
+
+    #[test]
+    fn evaluate_timer_deltas_accepts_a_varying_run() {
+        let deltas: Vec<u64> = (0..TIMER_TEST_SAMPLES as u64).collect();
+        assert_eq!(evaluate_timer_deltas(&deltas), Ok(()));
+    }
+
+    #[test]
+    fn f64_from_bits_always_lands_in_the_unit_interval() {
+        let patterns = [
+            0u64,
+            u64::MAX,
+            1,
+            u64::MAX - 1,
+            0xAAAA_AAAA_AAAA_AAAA,
+            0x5555_5555_5555_5555,
+            1u64 << 52,
+            (1u64 << 52) - 1,
+        ];
+        for &bits in &patterns {
+            let value = f64_from_bits(bits);
+            assert!(
+                (0.0..1.0).contains(&value),
+                "bits {bits:#x} produced out-of-range value {value}"
+            );
+        }
+
+        for seed in 0..10_000u64 {
+            let bits = seed.wrapping_mul(BACKGROUND_NOISE) ^ seed.rotate_left(17);
+            let value = f64_from_bits(bits);
+            assert!(
+                (0.0..1.0).contains(&value),
+                "bits {bits:#x} produced out-of-range value {value}"
+            );
+        }
+    }
+
+    #[test]
+    fn f32_from_bits_always_lands_in_the_unit_interval() {
+        let patterns = [
+            0u32,
+            u32::MAX,
+            1,
+            u32::MAX - 1,
+            0xAAAA_AAAA,
+            0x5555_5555,
+            1u32 << 23,
+            (1u32 << 23) - 1,
+        ];
+        for &bits in &patterns {
+            let value = f32_from_bits(bits);
+            assert!(
+                (0.0..1.0).contains(&value),
+                "bits {bits:#x} produced out-of-range value {value}"
+            );
+        }
+
+        for seed in 0..10_000u32 {
+            let bits = seed.wrapping_mul(0x9E37_79B9) ^ seed.rotate_left(13);
+            let value = f32_from_bits(bits);
+            assert!(
+                (0.0..1.0).contains(&value),
+                "bits {bits:#x} produced out-of-range value {value}"
+            );
+        }
+    }
+}
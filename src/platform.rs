@@ -0,0 +1,107 @@
+//! Portable timer backends. `_rdtsc` is x86_64-only, so this module picks
+//! the best tick source available for the target architecture, mirroring
+//! rand_jitter's own `platform.rs`.
+
+/// A monotonic tick source used to measure CPU jitter. Two back-to-back
+/// [`read`](Timer::read) calls establish a delta used as a raw entropy
+/// sample.
+pub(crate) trait Timer {
+    /// Returns a single counter reading.
+    fn read() -> u64;
+}
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) struct Rdtsc;
+
+#[cfg(target_arch = "x86_64")]
+impl Timer for Rdtsc {
+    fn read() -> u64 {
+        unsafe { std::arch::x86_64::_rdtsc() }
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+pub(crate) struct VirtualCounter;
+
+#[cfg(target_arch = "aarch64")]
+impl Timer for VirtualCounter {
+    fn read() -> u64 {
+        let tick: u64;
+        unsafe {
+            std::arch::asm!("mrs {tick}, cntvct_el0", tick = out(reg) tick);
+        }
+        tick
+    }
+}
+
+#[cfg(target_arch = "riscv64")]
+pub(crate) struct RdCycle;
+
+#[cfg(target_arch = "riscv64")]
+impl Timer for RdCycle {
+    fn read() -> u64 {
+        let tick: u64;
+        unsafe {
+            std::arch::asm!("rdcycle {tick}", tick = out(reg) tick);
+        }
+        tick
+    }
+}
+
+/// A portable fallback for targets without a cheap cycle counter (e.g.
+/// wasm): a nanosecond counter derived from [`std::time::Instant`].
+#[cfg(not(any(
+    target_arch = "x86_64",
+    target_arch = "aarch64",
+    target_arch = "riscv64"
+)))]
+pub(crate) struct MonotonicClock;
+
+#[cfg(not(any(
+    target_arch = "x86_64",
+    target_arch = "aarch64",
+    target_arch = "riscv64"
+)))]
+impl Timer for MonotonicClock {
+    fn read() -> u64 {
+        use std::sync::OnceLock;
+        use std::time::Instant;
+
+        static EPOCH: OnceLock<Instant> = OnceLock::new();
+        EPOCH.get_or_init(Instant::now).elapsed().as_nanos() as u64
+    }
+}
+
+/// The [`Timer`] backend selected for the current target architecture.
+#[cfg(target_arch = "x86_64")]
+pub(crate) type ActiveTimer = Rdtsc;
+
+#[cfg(target_arch = "aarch64")]
+pub(crate) type ActiveTimer = VirtualCounter;
+
+#[cfg(target_arch = "riscv64")]
+pub(crate) type ActiveTimer = RdCycle;
+
+#[cfg(not(any(
+    target_arch = "x86_64",
+    target_arch = "aarch64",
+    target_arch = "riscv64"
+)))]
+pub(crate) type ActiveTimer = MonotonicClock;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hint::black_box;
+
+    #[test]
+    fn active_timer_advances_between_reads() {
+        let t1 = ActiveTimer::read();
+        for i in 0..10_000u64 {
+            black_box(i);
+        }
+        let t2 = ActiveTimer::read();
+        assert!(t2 >= t1, "timer should not run backwards");
+        assert_ne!(t1, t2, "timer should advance across a busy loop");
+    }
+}
@@ -0,0 +1,197 @@
+//! A [`ReseedingJitter`] adapter that stretches [`Jitterbug`] over a fast
+//! CSPRNG, mirroring the reseeding pattern used by rand's `ReseedingRng`.
+
+use crate::{JitterError, Jitterbug};
+use rand_core::{CryptoRng, RngCore, SeedableRng, TryCryptoRng, TryRngCore};
+
+/// The default number of output bytes generated from the inner PRNG
+/// before [`ReseedingJitter`] reseeds it from a fresh
+/// [`Jitterbug`] harvest.
+const DEFAULT_RESEED_THRESHOLD: u64 = 1024 * 1024;
+
+/// [`ReseedingJitter`] seeds a fast PRNG `R` from [`Jitterbug`] and
+/// transparently reseeds it from a fresh harvest once a configurable byte
+/// threshold is crossed. This lets callers get fast, forward-secret
+/// output backed by a true-entropy seed, at the cost of touching the
+/// expensive jitter path only on reseed rather than on every call.
+pub struct ReseedingJitter<R: SeedableRng + RngCore> {
+    /// [`inner`](ReseedingJitter::inner) is the fast PRNG that serves bulk
+    /// generation between reseeds.
+    inner: R,
+    /// [`jitter`](ReseedingJitter::jitter) is the true-entropy source used
+    /// to seed and reseed [`inner`](ReseedingJitter::inner).
+    jitter: Jitterbug,
+    /// [`threshold`](ReseedingJitter::threshold) is the number of output
+    /// bytes allowed before a reseed. [`account_for`](ReseedingJitter::account_for)
+    /// checks this *before* the inner PRNG generates the current call's
+    /// bytes, so a single oversized [`try_fill_bytes`](ReseedingJitter::try_fill_bytes)
+    /// call that pushes `generated` past `threshold` reseeds immediately
+    /// and is served entirely by the freshly-reseeded inner, never a
+    /// stale one. That call's own byte count is credited against the new
+    /// seed's budget (instead of being discarded), so a run of
+    /// consecutively oversized calls doesn't undercount how much each
+    /// fresh seed has actually served.
+    threshold: u64,
+    /// [`generated`](ReseedingJitter::generated) is the number of output
+    /// bytes served since the last reseed.
+    generated: u64,
+}
+
+impl<R: SeedableRng + RngCore> ReseedingJitter<R> {
+    /// Creates a `new` [`ReseedingJitter`], seeding `R` from `jitter`
+    /// immediately and reseeding every `DEFAULT_RESEED_THRESHOLD` bytes
+    /// thereafter. Use [`with_threshold`](ReseedingJitter::with_threshold)
+    /// to configure a different threshold.
+    /// ## Usage
+    /// ```rust,ignore
+    /// // `FastRng` is any `SeedableRng + RngCore`, e.g. a ChaCha-based CSPRNG.
+    /// use jitterbug::{Jitterbug, ReseedingJitter};
+    ///
+    /// let mut rng = ReseedingJitter::<FastRng>::new(Jitterbug::new())
+    ///     .expect("jitter source should seed the inner PRNG");
+    /// let random_number = rng.try_next_u64().expect("inner PRNG should not fail");
+    /// println!("random number: {random_number}");
+    /// ```
+    pub fn new(jitter: Jitterbug) -> Result<Self, JitterError> {
+        Self::new_with_threshold(jitter, DEFAULT_RESEED_THRESHOLD)
+    }
+
+    /// Creates a `new` [`ReseedingJitter`] with a custom reseed
+    /// `threshold`, in bytes.
+    pub fn new_with_threshold(mut jitter: Jitterbug, threshold: u64) -> Result<Self, JitterError> {
+        let inner = Self::reseed_from(&mut jitter)?;
+        Ok(Self {
+            inner,
+            jitter,
+            threshold,
+            generated: 0,
+        })
+    }
+
+    /// Sets the reseed `threshold`, in bytes.
+    pub fn with_threshold(mut self, threshold: u64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    fn reseed_from(jitter: &mut Jitterbug) -> Result<R, JitterError> {
+        let mut seed = R::Seed::default();
+        jitter.try_fill_bytes(seed.as_mut())?;
+        Ok(R::from_seed(seed))
+    }
+
+    fn account_for(&mut self, bytes: u64) -> Result<(), JitterError> {
+        self.generated += bytes;
+        if self.generated >= self.threshold {
+            self.inner = Self::reseed_from(&mut self.jitter)?;
+            self.generated = bytes;
+        }
+        Ok(())
+    }
+}
+
+/// Implement [`TryRngCore`] for [`ReseedingJitter`]
+impl<R: SeedableRng + RngCore> TryRngCore for ReseedingJitter<R> {
+    type Error = JitterError;
+
+    fn try_next_u32(&mut self) -> Result<u32, Self::Error> {
+        self.account_for(4)?;
+        Ok(self.inner.next_u32())
+    }
+
+    fn try_next_u64(&mut self) -> Result<u64, Self::Error> {
+        self.account_for(8)?;
+        Ok(self.inner.next_u64())
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Self::Error> {
+        self.account_for(dest.len() as u64)?;
+        self.inner.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Implement [`TryCryptoRng`] for [`ReseedingJitter`], but only when the
+/// inner PRNG `R` is itself a [`CryptoRng`]: stretching [`Jitterbug`]'s
+/// true entropy over a predictable PRNG (e.g. a Pcg or Xoshiro generator)
+/// must not be mislabeled as cryptographically secure.
+impl<R: SeedableRng + RngCore + CryptoRng> TryCryptoRng for ReseedingJitter<R> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A deterministic counter-based PRNG, used only to exercise
+    /// [`ReseedingJitter`]'s threshold accounting without depending on the
+    /// quality of `R`'s own output.
+    struct CountingRng(u64);
+
+    impl RngCore for CountingRng {
+        fn next_u32(&mut self) -> u32 {
+            self.0 = self.0.wrapping_add(1);
+            self.0 as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0 = self.0.wrapping_add(1);
+            self.0
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for byte in dest.iter_mut() {
+                self.0 = self.0.wrapping_add(1);
+                *byte = self.0 as u8;
+            }
+        }
+    }
+
+    impl SeedableRng for CountingRng {
+        type Seed = [u8; 8];
+
+        fn from_seed(seed: Self::Seed) -> Self {
+            CountingRng(u64::from_le_bytes(seed))
+        }
+    }
+
+    #[test]
+    fn account_for_accumulates_below_threshold_without_reseeding() {
+        let mut rng = ReseedingJitter::<CountingRng>::new_with_threshold(Jitterbug::new(), 100)
+            .expect("jitter source should seed the inner PRNG");
+
+        rng.account_for(4).expect("well under threshold");
+        assert_eq!(rng.generated, 4);
+        rng.account_for(4).expect("still under threshold");
+        assert_eq!(rng.generated, 8);
+    }
+
+    #[test]
+    fn account_for_reseeds_and_credits_the_triggering_call_at_threshold() {
+        let mut rng = ReseedingJitter::<CountingRng>::new_with_threshold(Jitterbug::new(), 4)
+            .expect("jitter source should seed the inner PRNG");
+        let state_before = rng.inner.0;
+
+        rng.account_for(4)
+            .expect("crossing the threshold should reseed rather than fail");
+        assert_ne!(
+            rng.inner.0, state_before,
+            "reaching the threshold should replace the inner PRNG with a freshly seeded one"
+        );
+        assert_eq!(
+            rng.generated, 4,
+            "the triggering call's own bytes should be credited against the new seed, not discarded"
+        );
+    }
+
+    #[test]
+    fn account_for_credits_an_oversized_call_past_threshold() {
+        let mut rng = ReseedingJitter::<CountingRng>::new_with_threshold(Jitterbug::new(), 4)
+            .expect("jitter source should seed the inner PRNG");
+
+        rng.account_for(10)
+            .expect("an oversized call should reseed, not fail");
+        assert_eq!(
+            rng.generated, 10,
+            "an oversized call is served entirely by the fresh seed, so all of its bytes count toward it"
+        );
+    }
+}